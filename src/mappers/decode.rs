@@ -0,0 +1,192 @@
+//! Mappers that decompress a body before handing it to an inner mapper.
+//!
+//! These sit between [`body`](request/fn.body.html) and a downstream
+//! `Mapper<[u8]>`, decompressing the raw slice fully into an owned buffer
+//! before mapping, e.g. `request::body(gzip_decoded(json_decoded(eq(...))))`.
+//! A decode error fails the match (the inner `Out`'s default) rather than
+//! panicking. Requires the `compression` feature.
+#![cfg(feature = "compression")]
+
+use super::{mapper_name, Mapper};
+use std::fmt;
+use std::io::Read;
+
+pub(crate) fn decode_gzip(input: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    flate2::read::GzDecoder::new(input).read_to_end(&mut out)?;
+    Ok(out)
+}
+
+// `Content-Encoding: deflate` is ambiguous in the wild: some clients send a
+// zlib-wrapped stream (RFC 1950) and others a raw DEFLATE stream (RFC 1951).
+// Try zlib first and fall back to raw DEFLATE so either form decodes.
+pub(crate) fn decode_deflate(input: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    match flate2::read::ZlibDecoder::new(input).read_to_end(&mut out) {
+        Ok(_) => Ok(out),
+        Err(_) => {
+            out.clear();
+            flate2::read::DeflateDecoder::new(input).read_to_end(&mut out)?;
+            Ok(out)
+        }
+    }
+}
+
+pub(crate) fn decode_brotli(input: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    brotli::Decompressor::new(input, 4096).read_to_end(&mut out)?;
+    Ok(out)
+}
+
+/// Gzip-decompress the body and pass the decoded bytes to the next mapper.
+///
+/// # Example
+///
+/// ```no_run
+/// use httptest::mappers::*;
+///
+/// // Match a gzip-compressed json body of `{"a": 1}`.
+/// request::body(gzip_decoded(json_decoded(eq(serde_json::json!({"a": 1})))));
+/// ```
+pub fn gzip_decoded<M>(inner: M) -> GzipDecoded<M> {
+    GzipDecoded(inner)
+}
+/// The `GzipDecoded` mapper returned by [gzip_decoded()](fn.gzip_decoded.html)
+#[derive(Debug)]
+pub struct GzipDecoded<M>(M);
+impl<M> Mapper<[u8]> for GzipDecoded<M>
+where
+    M: Mapper<[u8]>,
+    M::Out: Default,
+{
+    type Out = M::Out;
+
+    fn map(&mut self, input: &[u8]) -> M::Out {
+        match decode_gzip(input) {
+            Ok(decoded) => self.0.map(&decoded),
+            Err(_) => M::Out::default(),
+        }
+    }
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("GzipDecoded")
+            .field(&mapper_name(&self.0))
+            .finish()
+    }
+}
+
+/// Deflate-decompress the body and pass the decoded bytes to the next mapper.
+///
+/// Accepts both zlib-wrapped (RFC 1950) and raw (RFC 1951) DEFLATE streams, as
+/// HTTP clients disagree on which to send for `Content-Encoding: deflate`.
+pub fn deflate_decoded<M>(inner: M) -> DeflateDecoded<M> {
+    DeflateDecoded(inner)
+}
+/// The `DeflateDecoded` mapper returned by [deflate_decoded()](fn.deflate_decoded.html)
+#[derive(Debug)]
+pub struct DeflateDecoded<M>(M);
+impl<M> Mapper<[u8]> for DeflateDecoded<M>
+where
+    M: Mapper<[u8]>,
+    M::Out: Default,
+{
+    type Out = M::Out;
+
+    fn map(&mut self, input: &[u8]) -> M::Out {
+        match decode_deflate(input) {
+            Ok(decoded) => self.0.map(&decoded),
+            Err(_) => M::Out::default(),
+        }
+    }
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("DeflateDecoded")
+            .field(&mapper_name(&self.0))
+            .finish()
+    }
+}
+
+/// Brotli-decompress the body and pass the decoded bytes to the next mapper.
+pub fn brotli_decoded<M>(inner: M) -> BrotliDecoded<M> {
+    BrotliDecoded(inner)
+}
+/// The `BrotliDecoded` mapper returned by [brotli_decoded()](fn.brotli_decoded.html)
+#[derive(Debug)]
+pub struct BrotliDecoded<M>(M);
+impl<M> Mapper<[u8]> for BrotliDecoded<M>
+where
+    M: Mapper<[u8]>,
+    M::Out: Default,
+{
+    type Out = M::Out;
+
+    fn map(&mut self, input: &[u8]) -> M::Out {
+        match decode_brotli(input) {
+            Ok(decoded) => self.0.map(&decoded),
+            Err(_) => M::Out::default(),
+        }
+    }
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("BrotliDecoded")
+            .field(&mapper_name(&self.0))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut enc = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        enc.write_all(data).unwrap();
+        enc.finish().unwrap()
+    }
+
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut enc = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        enc.write_all(data).unwrap();
+        enc.finish().unwrap()
+    }
+
+    fn raw_deflate(data: &[u8]) -> Vec<u8> {
+        let mut enc = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        enc.write_all(data).unwrap();
+        enc.finish().unwrap()
+    }
+
+    fn brotli(data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        {
+            let mut enc = brotli::CompressorWriter::new(&mut out, 4096, 5, 22);
+            enc.write_all(data).unwrap();
+        }
+        out
+    }
+
+    #[test]
+    fn test_gzip_decoded() {
+        let compressed = gzip(b"hello world");
+        assert!(gzip_decoded("hello world").map(&compressed[..]));
+        assert!(!gzip_decoded("goodbye").map(&compressed[..]));
+        // a decode error fails the match rather than panicking.
+        assert!(!gzip_decoded("hello world").map(&b"not gzip"[..]));
+    }
+
+    #[test]
+    fn test_deflate_decoded() {
+        // both zlib-wrapped and raw DEFLATE streams decode.
+        assert!(deflate_decoded("hello world").map(&zlib(b"hello world")[..]));
+        assert!(deflate_decoded("hello world").map(&raw_deflate(b"hello world")[..]));
+        assert!(!deflate_decoded("hello world").map(&b"garbage"[..]));
+    }
+
+    #[test]
+    fn test_brotli_decoded() {
+        let compressed = brotli(b"hello world");
+        assert!(brotli_decoded("hello world").map(&compressed[..]));
+        assert!(!brotli_decoded("hello world").map(&b"not brotli"[..]));
+    }
+}
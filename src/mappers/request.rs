@@ -2,6 +2,7 @@
 
 use super::{mapper_name, Mapper, KV};
 use std::fmt;
+use std::net::SocketAddr;
 
 /// Extract the method from the HTTP request and pass it to the next mapper.
 pub fn method<M>(inner: M) -> Method<M> {
@@ -175,6 +176,69 @@ where
     }
 }
 
+/// Extract the cookies from the HTTP request and pass the sequence to the next
+/// mapper.
+///
+/// Every `Cookie` header is parsed: the value is split on `;`, each pair is
+/// trimmed and split on the first `=` into a key and value. Multiple `Cookie`
+/// headers are concatenated. A pair with no `=` maps to an empty value, and
+/// surrounding quotes on values are preserved verbatim.
+///
+/// # Example
+///
+/// ```
+/// use httptest::mappers::*;
+///
+/// // A request matcher that matches a request with the cookie `session=abc123`.
+/// request::cookies(contains(("session", "abc123")));
+///
+/// // A request matcher that matches a request carrying a `csrf` cookie with any value.
+/// request::cookies(contains(key("csrf")));
+/// ```
+pub fn cookies<M>(inner: M) -> Cookies<M> {
+    Cookies(inner)
+}
+/// The `Cookies` mapper returned by [cookies()](fn.cookies.html)
+#[derive(Debug)]
+pub struct Cookies<M>(M);
+impl<M, B> Mapper<http::Request<B>> for Cookies<M>
+where
+    M: Mapper<[KV<str, str>]>,
+{
+    type Out = M::Out;
+
+    fn map(&mut self, input: &http::Request<B>) -> M::Out {
+        let cookies: Vec<KV<str, str>> = input
+            .headers()
+            .get_all(http::header::COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(';'))
+            .filter_map(|pair| {
+                let pair = pair.trim();
+                if pair.is_empty() {
+                    return None;
+                }
+                let (k, v) = match pair.find('=') {
+                    Some(idx) => (&pair[..idx], &pair[idx + 1..]),
+                    None => (pair, ""),
+                };
+                Some(KV {
+                    k: k.to_owned(),
+                    v: v.to_owned(),
+                })
+            })
+            .collect();
+        self.0.map(&cookies)
+    }
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Cookies")
+            .field(&mapper_name(&self.0))
+            .finish()
+    }
+}
+
 /// A convenience matcher for both method and path. Extracts a bolean true if the method and path both match.
 ///
 /// `method_path(a, b) == all_of![method(a), path(b)]`
@@ -215,6 +279,139 @@ where
     }
 }
 
+/// Match a CORS preflight request: an `OPTIONS` request carrying an
+/// `Access-Control-Request-Method` header.
+///
+/// # Example
+///
+/// ```
+/// use httptest::mappers::*;
+///
+/// // A request matcher that matches any CORS preflight request.
+/// request::is_preflight();
+/// ```
+pub fn is_preflight() -> IsPreflight {
+    IsPreflight
+}
+/// The `IsPreflight` mapper returned by [is_preflight()](fn.is_preflight.html)
+#[derive(Debug)]
+pub struct IsPreflight;
+impl<B> Mapper<http::Request<B>> for IsPreflight {
+    type Out = bool;
+
+    fn map(&mut self, input: &http::Request<B>) -> bool {
+        input.method() == http::Method::OPTIONS
+            && input
+                .headers()
+                .contains_key(http::header::ACCESS_CONTROL_REQUEST_METHOD)
+    }
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IsPreflight").finish()
+    }
+}
+
+/// Extract the client's remote socket address from the HTTP request and pass
+/// it to the next mapper.
+///
+/// The address is stashed into the request's extensions by the server when the
+/// connection is accepted; the match fails if no address is present.
+///
+/// # Example
+///
+/// ```
+/// use httptest::mappers::*;
+///
+/// // A request matcher that matches a connection from loopback port 1234.
+/// request::remote_addr(eq("127.0.0.1:1234".parse::<std::net::SocketAddr>().unwrap()));
+/// ```
+pub fn remote_addr<M>(inner: M) -> RemoteAddr<M> {
+    RemoteAddr(inner)
+}
+/// The `RemoteAddr` mapper returned by [remote_addr()](fn.remote_addr.html)
+#[derive(Debug)]
+pub struct RemoteAddr<M>(M);
+impl<M, B> Mapper<http::Request<B>> for RemoteAddr<M>
+where
+    M: Mapper<SocketAddr, Out = bool>,
+{
+    type Out = bool;
+
+    fn map(&mut self, input: &http::Request<B>) -> bool {
+        match input.extensions().get::<SocketAddr>() {
+            Some(addr) => self.0.map(addr),
+            None => false,
+        }
+    }
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("RemoteAddr")
+            .field(&mapper_name(&self.0))
+            .finish()
+    }
+}
+
+/// Decompress the body according to the request's `Content-Encoding` header
+/// and pass the decoded bytes to the next mapper.
+///
+/// The algorithm is chosen from the `Content-Encoding` header (`gzip`,
+/// `deflate` or `br`); a missing or `identity` encoding passes the body
+/// through untouched. An unknown encoding or a decode error fails the match.
+/// Requires the `compression` feature.
+///
+/// # Example
+///
+/// ```no_run
+/// use httptest::mappers::*;
+///
+/// // Match a json body of `{"a": 1}` regardless of how the client encoded it.
+/// request::auto_decoded(json_decoded(eq(serde_json::json!({"a": 1}))));
+/// ```
+#[cfg(feature = "compression")]
+pub fn auto_decoded<M>(inner: M) -> AutoDecoded<M> {
+    AutoDecoded(inner)
+}
+/// The `AutoDecoded` mapper returned by [auto_decoded()](fn.auto_decoded.html)
+#[cfg(feature = "compression")]
+#[derive(Debug)]
+pub struct AutoDecoded<M>(M);
+#[cfg(feature = "compression")]
+impl<M, B> Mapper<http::Request<B>> for AutoDecoded<M>
+where
+    B: AsRef<[u8]>,
+    M: Mapper<[u8]>,
+    M::Out: Default,
+{
+    type Out = M::Out;
+
+    fn map(&mut self, input: &http::Request<B>) -> M::Out {
+        use super::decode::{decode_brotli, decode_deflate, decode_gzip};
+        let body = input.body().as_ref();
+        let encoding = input
+            .headers()
+            .get(http::header::CONTENT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim().to_ascii_lowercase());
+        let decoded = match encoding.as_deref() {
+            Some("gzip") | Some("x-gzip") => decode_gzip(body),
+            Some("deflate") => decode_deflate(body),
+            Some("br") => decode_brotli(body),
+            None | Some("identity") | Some("") => Ok(body.to_owned()),
+            Some(_) => return M::Out::default(),
+        };
+        match decoded {
+            Ok(decoded) => self.0.map(&decoded),
+            Err(_) => M::Out::default(),
+        }
+    }
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("AutoDecoded")
+            .field(&mapper_name(&self.0))
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -288,6 +485,75 @@ mod tests {
         assert!(body("my request body").map(&req));
     }
 
+    #[test]
+    fn test_cookies() {
+        let mut req = http::Request::get("https://example.com/foo")
+            .body("")
+            .unwrap();
+        req.headers_mut().extend(vec![
+            (
+                http::header::COOKIE,
+                http::HeaderValue::from_static("session=abc123; csrf=\"tok\""),
+            ),
+            (
+                http::header::COOKIE,
+                http::HeaderValue::from_static("flag"),
+            ),
+        ]);
+
+        // key/value pairs are extracted, quotes preserved, bare pairs map to empty.
+        assert!(cookies(contains(("session", "abc123"))).map(&req));
+        assert!(cookies(contains(("csrf", "\"tok\""))).map(&req));
+        assert!(cookies(contains(("flag", ""))).map(&req));
+        assert!(cookies(contains(key("csrf"))).map(&req));
+        assert!(!cookies(contains(key("missing"))).map(&req));
+    }
+
+    #[test]
+    fn test_remote_addr() {
+        let addr: std::net::SocketAddr = "127.0.0.1:1234".parse().unwrap();
+        let mut req = http::Request::get("http://localhost/foo").body("").unwrap();
+        req.extensions_mut().insert(addr);
+        assert!(remote_addr(eq(addr)).map(&req));
+        assert!(!remote_addr(eq("10.0.0.1:80".parse::<std::net::SocketAddr>().unwrap())).map(&req));
+
+        // no address present in the extensions -> no match.
+        let req = http::Request::get("http://localhost/foo").body("").unwrap();
+        assert!(!remote_addr(eq(addr)).map(&req));
+    }
+
+    #[test]
+    fn test_is_preflight() {
+        let mut req = http::Request::builder()
+            .method("OPTIONS")
+            .uri("https://example.com/foo")
+            .body("")
+            .unwrap();
+        req.headers_mut().insert(
+            http::header::ACCESS_CONTROL_REQUEST_METHOD,
+            http::HeaderValue::from_static("POST"),
+        );
+        assert!(is_preflight().map(&req));
+
+        // An OPTIONS request without the preflight header does not match.
+        let req = http::Request::builder()
+            .method("OPTIONS")
+            .uri("https://example.com/foo")
+            .body("")
+            .unwrap();
+        assert!(!is_preflight().map(&req));
+
+        // A non-OPTIONS request does not match.
+        let mut req = http::Request::get("https://example.com/foo")
+            .body("")
+            .unwrap();
+        req.headers_mut().insert(
+            http::header::ACCESS_CONTROL_REQUEST_METHOD,
+            http::HeaderValue::from_static("POST"),
+        );
+        assert!(!is_preflight().map(&req));
+    }
+
     #[test]
     fn test_method_path() {
         let req = http::Request::get("https://example.com/foo")
@@ -0,0 +1,524 @@
+//! Responders return a response to the client when an expectation is matched.
+
+use hyper::body::Bytes;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// A future resolving to the response sent back to the client.
+type ResponseFuture =
+    Pin<Box<dyn Future<Output = http::Response<hyper::Body>> + Send + 'static>>;
+
+/// Respond to a matched request.
+pub trait Responder: fmt::Debug + Send {
+    /// Return the response for a match.
+    fn respond(&mut self) -> ResponseFuture;
+
+    /// Return the response for the `index`-th (0-based) match of the
+    /// expectation.
+    ///
+    /// The default ignores the index and defers to
+    /// [respond](#tymethod.respond). Responders that vary their reply per hit
+    /// (e.g. [cycle()](fn.cycle.html)) override this.
+    fn respond_at(&mut self, _index: usize) -> ResponseFuture {
+        self.respond()
+    }
+
+    /// Return the response for the `index`-th match, given the request that
+    /// matched.
+    ///
+    /// The default ignores the request and defers to
+    /// [respond_at](#method.respond_at). Responders whose reply depends on the
+    /// request (e.g. [cors()](fn.cors.html), which echoes the matching
+    /// `Origin`) override this.
+    fn respond_to(
+        &mut self,
+        _req: &http::Request<hyper::body::Bytes>,
+        index: usize,
+    ) -> ResponseFuture {
+        self.respond_at(index)
+    }
+}
+
+// Rebuild an owned response backed by a `hyper::Body`, copying the head and
+// cloning the body. `http::Response` is not `Clone` because its extensions are
+// not, so copy the cloneable parts explicitly.
+fn build_response<B>(resp: &http::Response<B>) -> http::Response<hyper::Body>
+where
+    B: Clone + Into<hyper::Body>,
+{
+    let mut builder = http::Response::builder()
+        .status(resp.status())
+        .version(resp.version());
+    if let Some(headers) = builder.headers_mut() {
+        *headers = resp.headers().clone();
+    }
+    builder.body(resp.body().clone().into()).unwrap()
+}
+
+impl<B> Responder for http::Response<B>
+where
+    B: Clone + Into<hyper::Body> + Send + fmt::Debug + 'static,
+{
+    fn respond(&mut self) -> ResponseFuture {
+        let resp = build_response(self);
+        Box::pin(async move { resp })
+    }
+}
+
+/// Respond with the given status code and an empty body.
+///
+/// # Example
+///
+/// ```
+/// use httptest::responders::*;
+///
+/// // A responder that replies `200 OK`.
+/// status_code(200);
+/// ```
+pub fn status_code(code: u16) -> http::Response<Bytes> {
+    http::Response::builder()
+        .status(code)
+        .body(Bytes::new())
+        .unwrap()
+}
+
+/// Respond with a json encoded body.
+pub fn json_encoded<T>(data: T) -> http::Response<Bytes>
+where
+    T: serde::Serialize,
+{
+    let body = serde_json::to_vec(&data).expect("failed to serialize json body");
+    http::Response::builder()
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Bytes::from(body))
+        .unwrap()
+}
+
+/// Respond with a url encoded body.
+pub fn url_encoded<T>(data: T) -> http::Response<Bytes>
+where
+    T: serde::Serialize,
+{
+    let body = serde_urlencoded::to_string(&data).expect("failed to serialize url encoded body");
+    http::Response::builder()
+        .header(
+            http::header::CONTENT_TYPE,
+            "application/x-www-form-urlencoded",
+        )
+        .body(Bytes::from(body))
+        .unwrap()
+}
+
+/// Respond with a different responder on each successive match.
+///
+/// Given responders `[r0, r1, r2]`, the Nth matching request (0-indexed)
+/// responds with `r[N % len]`, cycling back to the start once exhausted. Use
+/// [Cycle::saturating](struct.Cycle.html#method.saturating) to stick on the
+/// last responder instead of wrapping.
+///
+/// # Example
+///
+/// ```
+/// use httptest::responders::*;
+///
+/// // Respond `503` twice, then `200` on every subsequent match.
+/// cycle(vec![
+///     Box::new(status_code(503)),
+///     Box::new(status_code(503)),
+///     Box::new(status_code(200)),
+/// ])
+/// .saturating();
+/// ```
+pub fn cycle(responders: Vec<Box<dyn Responder>>) -> Cycle {
+    assert!(
+        !responders.is_empty(),
+        "cycle() requires at least one responder"
+    );
+    Cycle {
+        responders,
+        saturating: false,
+    }
+}
+
+/// The `Cycle` responder returned by [cycle()](fn.cycle.html).
+#[derive(Debug)]
+pub struct Cycle {
+    responders: Vec<Box<dyn Responder>>,
+    saturating: bool,
+}
+
+impl Cycle {
+    /// Stick on the last responder once the sequence is exhausted rather than
+    /// wrapping back to the start.
+    pub fn saturating(mut self) -> Self {
+        self.saturating = true;
+        self
+    }
+}
+
+impl Responder for Cycle {
+    fn respond(&mut self) -> ResponseFuture {
+        self.respond_at(0)
+    }
+
+    fn respond_at(&mut self, index: usize) -> ResponseFuture {
+        let len = self.responders.len();
+        let idx = if self.saturating {
+            index.min(len - 1)
+        } else {
+            index % len
+        };
+        self.responders[idx].respond_at(index)
+    }
+}
+
+/// Wait for `duration` before producing the wrapped response.
+///
+/// Useful for exercising client-side timeout and retry logic against a slow
+/// peer.
+///
+/// # Example
+///
+/// ```
+/// use httptest::responders::*;
+/// use std::time::Duration;
+///
+/// // Reply `200 OK` after a two second delay.
+/// delay(Duration::from_secs(2), status_code(200));
+/// ```
+pub fn delay<R>(duration: Duration, inner: R) -> Delay<R> {
+    Delay { duration, inner }
+}
+
+/// The `Delay` responder returned by [delay()](fn.delay.html).
+#[derive(Debug)]
+pub struct Delay<R> {
+    duration: Duration,
+    inner: R,
+}
+
+impl<R> Responder for Delay<R>
+where
+    R: Responder,
+{
+    fn respond(&mut self) -> ResponseFuture {
+        self.respond_at(0)
+    }
+
+    fn respond_at(&mut self, index: usize) -> ResponseFuture {
+        let duration = self.duration;
+        let inner = self.inner.respond_at(index);
+        Box::pin(async move {
+            tokio::time::delay_for(duration).await;
+            inner.await
+        })
+    }
+}
+
+/// Hold the connection open indefinitely without ever replying.
+///
+/// The request future never resolves, so the client must rely on its own
+/// timeout to give up. Handy for driving client-side timeout and retry paths.
+pub fn never_respond() -> NeverRespond {
+    NeverRespond
+}
+
+/// The `NeverRespond` responder returned by [never_respond()](fn.never_respond.html).
+#[derive(Debug)]
+pub struct NeverRespond;
+
+impl Responder for NeverRespond {
+    fn respond(&mut self) -> ResponseFuture {
+        Box::pin(futures::future::pending())
+    }
+}
+
+/// Hold the connection open for `duration` without replying, then answer with
+/// an empty `200 OK`.
+///
+/// Like [never_respond()](fn.never_respond.html) but bounded, so the server
+/// task does not stay pending forever after the test completes.
+pub fn hang_until(duration: Duration) -> HangUntil {
+    HangUntil { duration }
+}
+
+/// The `HangUntil` responder returned by [hang_until()](fn.hang_until.html).
+#[derive(Debug)]
+pub struct HangUntil {
+    duration: Duration,
+}
+
+impl Responder for HangUntil {
+    fn respond(&mut self) -> ResponseFuture {
+        let duration = self.duration;
+        Box::pin(async move {
+            tokio::time::delay_for(duration).await;
+            http::Response::builder()
+                .status(hyper::StatusCode::OK)
+                .body(hyper::Body::empty())
+                .unwrap()
+        })
+    }
+}
+
+/// Respond to a CORS preflight request with a `204 No Content` and the
+/// appropriate `Access-Control-Allow-*` headers.
+///
+/// Given a set of allowed origins, the responder echoes back the single
+/// `Access-Control-Allow-Origin` that matches the request's `Origin` header
+/// (rather than a wildcard); if the request's origin is not allowed, no
+/// `Access-Control-Allow-Origin` header is emitted. Typically paired with
+/// [request::is_preflight()](../mappers/request/fn.is_preflight.html).
+///
+/// # Example
+///
+/// ```
+/// use httptest::responders::*;
+///
+/// cors()
+///     .allow_origins(vec!["https://example.com", "https://example.org"])
+///     .allow_methods(vec!["GET", "POST"])
+///     .allow_headers(vec!["content-type"]);
+/// ```
+pub fn cors() -> Cors {
+    Cors {
+        allow_origins: Vec::new(),
+        allow_methods: Vec::new(),
+        allow_headers: Vec::new(),
+    }
+}
+
+/// The `Cors` responder returned by [cors()](fn.cors.html).
+#[derive(Debug)]
+pub struct Cors {
+    allow_origins: Vec<String>,
+    allow_methods: Vec<String>,
+    allow_headers: Vec<String>,
+}
+
+impl Cors {
+    /// Add a single origin to the set of allowed origins.
+    pub fn allow_origin(mut self, origin: impl Into<String>) -> Self {
+        self.allow_origins.push(origin.into());
+        self
+    }
+
+    /// Set the allowed origins. The one matching the request's `Origin` header
+    /// is echoed back in `Access-Control-Allow-Origin`.
+    pub fn allow_origins<I, S>(mut self, origins: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allow_origins = origins.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the methods advertised in `Access-Control-Allow-Methods`.
+    pub fn allow_methods<I, S>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allow_methods = methods.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the headers advertised in `Access-Control-Allow-Headers`.
+    pub fn allow_headers<I, S>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allow_headers = headers.into_iter().map(Into::into).collect();
+        self
+    }
+
+    // Pick the allowed origin matching the request's `Origin` header, if any.
+    fn matching_origin<B>(&self, req: &http::Request<B>) -> Option<String> {
+        let origin = req.headers().get(http::header::ORIGIN)?.to_str().ok()?;
+        self.allow_origins
+            .iter()
+            .find(|allowed| allowed.as_str() == origin)
+            .cloned()
+    }
+
+    fn build(&self, allow_origin: Option<&str>) -> http::Response<hyper::Body> {
+        let mut builder = http::Response::builder().status(hyper::StatusCode::NO_CONTENT);
+        if let Some(origin) = allow_origin {
+            builder = builder.header(http::header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        }
+        if !self.allow_methods.is_empty() {
+            builder = builder.header(
+                http::header::ACCESS_CONTROL_ALLOW_METHODS,
+                self.allow_methods.join(", "),
+            );
+        }
+        if !self.allow_headers.is_empty() {
+            builder = builder.header(
+                http::header::ACCESS_CONTROL_ALLOW_HEADERS,
+                self.allow_headers.join(", "),
+            );
+        }
+        builder.body(hyper::Body::empty()).unwrap()
+    }
+}
+
+impl Responder for Cors {
+    fn respond(&mut self) -> ResponseFuture {
+        // Without a request there is no origin to match against.
+        let resp = self.build(None);
+        Box::pin(async move { resp })
+    }
+
+    fn respond_to(
+        &mut self,
+        req: &http::Request<hyper::body::Bytes>,
+        _index: usize,
+    ) -> ResponseFuture {
+        let origin = self.matching_origin(req);
+        let resp = self.build(origin.as_deref());
+        Box::pin(async move { resp })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    fn status_sequence(responder: &mut dyn Responder, count: usize) -> Vec<u16> {
+        (0..count)
+            .map(|i| block_on(responder.respond_at(i)).status().as_u16())
+            .collect()
+    }
+
+    #[test]
+    fn test_cycle_wraps() {
+        // 503 twice then 200, wrapping back to the start.
+        let mut responder = cycle(vec![
+            Box::new(status_code(503)),
+            Box::new(status_code(503)),
+            Box::new(status_code(200)),
+        ]);
+        assert_eq!(
+            status_sequence(&mut responder, 7),
+            vec![503, 503, 200, 503, 503, 200, 503]
+        );
+    }
+
+    #[test]
+    fn test_cycle_saturating() {
+        // 503 twice then 200 forever once exhausted.
+        let mut responder = cycle(vec![
+            Box::new(status_code(503)),
+            Box::new(status_code(503)),
+            Box::new(status_code(200)),
+        ])
+        .saturating();
+        assert_eq!(
+            status_sequence(&mut responder, 5),
+            vec![503, 503, 200, 200, 200]
+        );
+    }
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_delay_defers_and_delegates() {
+        let mut rt = runtime();
+        rt.block_on(async {
+            let start = std::time::Instant::now();
+            let resp = delay(Duration::from_millis(20), status_code(201))
+                .respond()
+                .await;
+            assert!(start.elapsed() >= Duration::from_millis(20));
+            assert_eq!(resp.status().as_u16(), 201);
+        });
+    }
+
+    #[test]
+    fn test_never_respond_never_resolves() {
+        let mut rt = runtime();
+        rt.block_on(async {
+            let result = tokio::time::timeout(
+                Duration::from_millis(20),
+                never_respond().respond(),
+            )
+            .await;
+            assert!(result.is_err());
+        });
+    }
+
+    #[test]
+    fn test_hang_until_resolves_after_delay() {
+        let mut rt = runtime();
+        rt.block_on(async {
+            let start = std::time::Instant::now();
+            let resp = hang_until(Duration::from_millis(20)).respond().await;
+            assert!(start.elapsed() >= Duration::from_millis(20));
+            assert_eq!(resp.status().as_u16(), 200);
+        });
+    }
+
+    fn preflight(origin: &str) -> http::Request<hyper::body::Bytes> {
+        http::Request::builder()
+            .method("OPTIONS")
+            .uri("http://localhost/")
+            .header(http::header::ORIGIN, origin)
+            .header(http::header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(hyper::body::Bytes::new())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cors_echoes_matching_origin() {
+        let mut responder = cors()
+            .allow_origins(vec!["https://example.com", "https://example.org"])
+            .allow_methods(vec!["GET", "POST"])
+            .allow_headers(vec!["content-type"]);
+
+        // the single matching origin is echoed back, not a wildcard.
+        let resp = block_on(responder.respond_to(&preflight("https://example.org"), 0));
+        assert_eq!(resp.status(), hyper::StatusCode::NO_CONTENT);
+        let headers = resp.headers();
+        assert_eq!(
+            headers
+                .get(http::header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                .unwrap(),
+            "https://example.org"
+        );
+        assert_eq!(
+            headers
+                .get(http::header::ACCESS_CONTROL_ALLOW_METHODS)
+                .unwrap(),
+            "GET, POST"
+        );
+        assert_eq!(
+            headers
+                .get(http::header::ACCESS_CONTROL_ALLOW_HEADERS)
+                .unwrap(),
+            "content-type"
+        );
+    }
+
+    #[test]
+    fn test_cors_disallowed_origin_omits_header() {
+        let mut responder =
+            cors().allow_origins(vec!["https://example.com"]);
+        let resp = block_on(responder.respond_to(&preflight("https://evil.test"), 0));
+        assert_eq!(resp.status(), hyper::StatusCode::NO_CONTENT);
+        assert!(resp
+            .headers()
+            .get(http::header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none());
+    }
+}
@@ -33,8 +33,11 @@ impl Server {
         let state = ServerState::default();
         let make_service = make_service_fn({
             let state = state.clone();
-            move |_| {
+            move |conn: &hyper::server::conn::AddrStream| {
                 let state = state.clone();
+                // Capture the peer address of this connection so it can be
+                // exposed to mappers via the request's extensions.
+                let remote_addr = conn.remote_addr();
                 async move {
                     let state = state.clone();
                     Ok::<_, Error>(service_fn({
@@ -45,7 +48,8 @@ impl Server {
                                 // read the full body into memory prior to handing it to mappers.
                                 let (head, body) = req.into_parts();
                                 let full_body = hyper::body::to_bytes(body).await?;
-                                let req = http::Request::from_parts(head, full_body);
+                                let mut req = http::Request::from_parts(head, full_body);
+                                req.extensions_mut().insert(remote_addr);
                                 log::debug!("Received Request: {:?}", req);
                                 let resp = on_req(state, req).await;
                                 log::debug!("Sending Response: {:?}", resp);
@@ -115,6 +119,74 @@ impl Server {
         self.state.push_expectation(expectation);
     }
 
+    /// Enable or disable recording of received requests.
+    ///
+    /// Recording is off by default to preserve the server's normal memory
+    /// behavior. Once enabled every received request is retained and can be
+    /// retrieved via [received_requests](#method.received_requests) or
+    /// [requests_matching](#method.requests_matching). The recording is
+    /// cleared by [verify_and_clear](#method.verify_and_clear), which also
+    /// turns recording back off.
+    pub fn record_requests(&self, record: bool) {
+        self.state.lock().record = record;
+    }
+
+    /// Return a clone of every request the server has received since recording
+    /// was enabled.
+    ///
+    /// Recording must have been enabled via
+    /// [record_requests](#method.record_requests); otherwise this is empty.
+    /// Request extensions are not retained in the recorded copies.
+    pub fn received_requests(&self) -> Vec<FullRequest> {
+        self.state
+            .lock()
+            .recorded
+            .iter()
+            .map(|recorded| clone_full_request(&recorded.request))
+            .collect()
+    }
+
+    /// Return every recorded request together with whether it was served the
+    /// response of a matching expectation at the time it was received.
+    ///
+    /// Recording must have been enabled via
+    /// [record_requests](#method.record_requests); otherwise this is empty.
+    pub fn recorded_requests(&self) -> Vec<RecordedRequest> {
+        self.state
+            .lock()
+            .recorded
+            .iter()
+            .map(|recorded| RecordedRequest {
+                request: clone_full_request(&recorded.request),
+                matched: recorded.matched,
+            })
+            .collect()
+    }
+
+    /// Return a clone of every recorded request that matches `matcher`.
+    ///
+    /// Useful for asserting after the fact on details that are awkward to
+    /// encode up front, e.g. snapshotting an exact body or checking ordering
+    /// across endpoints. Recording must have been enabled via
+    /// [record_requests](#method.record_requests).
+    ///
+    /// Note that recorded requests do not retain their extensions, so mappers
+    /// that read the extensions (e.g.
+    /// [remote_addr](../mappers/request/fn.remote_addr.html)) will never match
+    /// a recorded request.
+    pub fn requests_matching(
+        &self,
+        mut matcher: impl Matcher<FullRequest>,
+    ) -> Vec<FullRequest> {
+        self.state
+            .lock()
+            .recorded
+            .iter()
+            .filter(|recorded| matcher.matches(&recorded.request))
+            .map(|recorded| clone_full_request(&recorded.request))
+            .collect()
+    }
+
     /// Verify all registered expectations. Panic if any are not met, then clear
     /// all expectations leaving the server running in a clean state.
     pub fn verify_and_clear(&mut self) {
@@ -156,6 +228,10 @@ async fn on_req(state: ServerState, req: FullRequest) -> http::Response<hyper::B
         // Iterate over expectations in reverse order. Expectations are
         // evaluated most recently added first.
         let mut iter = state.expected.iter_mut().rev();
+        // Whether the request was served its matched expectation's response. A
+        // request that matches a matcher but exceeds its cardinality is served
+        // an error instead, so it does not count as served.
+        let mut served = false;
         let response_future = loop {
             let expectation = match iter.next() {
                 None => break None,
@@ -165,7 +241,12 @@ async fn on_req(state: ServerState, req: FullRequest) -> http::Response<hyper::B
                 log::debug!("found matcher: {:?}", &expectation.matcher);
                 expectation.hit_count += 1;
                 if cardinality_not_exceeded(&expectation.cardinality, expectation.hit_count) {
-                    break Some(expectation.responder.respond());
+                    served = true;
+                    break Some(
+                        expectation
+                            .responder
+                            .respond_to(&req, expectation.hit_count - 1),
+                    );
                 } else {
                     break Some(Box::pin(cardinality_error(
                         &*expectation.matcher as &dyn Matcher<FullRequest>,
@@ -179,6 +260,12 @@ async fn on_req(state: ServerState, req: FullRequest) -> http::Response<hyper::B
             log::debug!("no matcher found for request: {:?}", req);
             state.unexpected_requests += 1;
         }
+        if state.record {
+            state.recorded.push(RecordedRequest {
+                request: clone_full_request(&req),
+                matched: served,
+            });
+        }
         response_future
     };
     if let Some(f) = response_future {
@@ -307,6 +394,8 @@ impl Default for ServerState {
 struct ServerStateInner {
     unexpected_requests: usize,
     expected: Vec<Expectation>,
+    record: bool,
+    recorded: Vec<RecordedRequest>,
 }
 
 impl Default for ServerStateInner {
@@ -314,10 +403,39 @@ impl Default for ServerStateInner {
         ServerStateInner {
             unexpected_requests: Default::default(),
             expected: Default::default(),
+            record: false,
+            recorded: Default::default(),
         }
     }
 }
 
+/// A request recorded by the server, along with whether it was served by a
+/// matching expectation when it was received.
+#[derive(Debug)]
+pub struct RecordedRequest {
+    /// The request as received. Its body is buffered into memory; extensions
+    /// are not retained.
+    pub request: FullRequest,
+    /// Whether the request was served its matched expectation's response. A
+    /// request that matched an expectation but exceeded its cardinality (and so
+    /// received an error response) is recorded as `false`.
+    pub matched: bool,
+}
+
+/// Clone a buffered request. `http::Request` is not itself `Clone` because its
+/// extensions are not, so copy the head and body explicitly and drop any
+/// extensions.
+fn clone_full_request(req: &FullRequest) -> FullRequest {
+    let mut builder = http::Request::builder()
+        .method(req.method().clone())
+        .uri(req.uri().clone())
+        .version(req.version());
+    if let Some(headers) = builder.headers_mut() {
+        *headers = req.headers().clone();
+    }
+    builder.body(req.body().clone()).unwrap()
+}
+
 fn cardinality_error(
     matcher: &dyn Matcher<FullRequest>,
     cardinality: &Times,
@@ -334,3 +452,64 @@ fn cardinality_error(
             .unwrap()
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mappers::request;
+    use crate::responders::status_code;
+    use futures::executor::block_on;
+
+    fn get(path: &str) -> FullRequest {
+        http::Request::get(format!("http://localhost{}", path))
+            .body(hyper::body::Bytes::new())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_recording_disabled_by_default() {
+        let state = ServerState::default();
+        block_on(on_req(state.clone(), get("/foo")));
+        assert!(state.lock().recorded.is_empty());
+    }
+
+    #[test]
+    fn test_recording_tracks_match() {
+        let state = ServerState::default();
+        state.lock().record = true;
+        state.push_expectation(
+            Expectation::matching(request::path("/foo")).respond_with(status_code(200)),
+        );
+
+        block_on(on_req(state.clone(), get("/foo")));
+        block_on(on_req(state.clone(), get("/bar")));
+
+        let inner = state.lock();
+        assert_eq!(inner.recorded.len(), 2);
+        // the matched flag reflects whether an expectation matched on receipt.
+        assert_eq!(inner.recorded[0].request.uri().path(), "/foo");
+        assert!(inner.recorded[0].matched);
+        assert_eq!(inner.recorded[1].request.uri().path(), "/bar");
+        assert!(!inner.recorded[1].matched);
+    }
+
+    #[test]
+    fn test_recording_cardinality_exceeded_not_matched() {
+        let state = ServerState::default();
+        state.lock().record = true;
+        // default cardinality is Exactly(1).
+        state.push_expectation(
+            Expectation::matching(request::path("/foo")).respond_with(status_code(200)),
+        );
+
+        block_on(on_req(state.clone(), get("/foo")));
+        block_on(on_req(state.clone(), get("/foo")));
+
+        let inner = state.lock();
+        assert_eq!(inner.recorded.len(), 2);
+        assert!(inner.recorded[0].matched);
+        // the second hit exceeds the cardinality and is served an error, so it
+        // is not recorded as matched.
+        assert!(!inner.recorded[1].matched);
+    }
+}